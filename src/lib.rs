@@ -7,7 +7,7 @@ mod test;
 pub mod testutils;
 
 use soroban_auth::{Identifier, Signature};
-use soroban_sdk::{contractimpl, contracttype, BigInt, BytesN, Env};
+use soroban_sdk::{contractimpl, contracttype, symbol, BigInt, BytesN, Env, IntoVal, RawVal, Symbol, Vec};
 
 mod token {
     soroban_sdk::contractimport!(file = "./soroban_token_spec.wasm");
@@ -24,6 +24,13 @@ pub enum DataKey {
     Timestamp,
     Slope,
     Nonce(Identifier),
+    State,
+    CurveKind,
+    DecayNumerator,
+    DecayDenominator,
+    Step,
+    LastSale,
+    InstantPrice,
 }
 
 #[derive(Clone)]
@@ -33,6 +40,44 @@ pub struct Auth {
     pub nonce: BigInt,
 }
 
+// The auction's lifecycle: `Pending` until the prize has been deposited and
+// `activate` is called, `Active` while the price decays and `buy` is open,
+// and finally `Settled` or `Cancelled` once it's done.
+#[derive(Clone, PartialEq)]
+#[contracttype]
+pub enum State {
+    Pending,
+    Active,
+    Settled,
+    Cancelled,
+}
+
+// The price-decay curve used by `compute_price`. `Exponential` decays by a
+// constant `DecayNumerator / DecayDenominator` ratio every `Step` seconds,
+// instead of the straight line `Linear` uses.
+#[derive(Clone, PartialEq)]
+#[contracttype]
+pub enum CurveKind {
+    Linear,
+    Exponential,
+}
+
+// Upper bound on how many decay steps `compute_price` will iterate for an
+// `Exponential` curve, so a long-idle auction can't make the call unbounded.
+const MAX_DECAY_STEPS: u64 = 128;
+
+// Record of a single purchase (full or partial), published as an event and
+// kept under `DataKey::LastSale` so off-chain indexers and read-only calls
+// can recover the clearing price without replaying every event.
+#[derive(Clone)]
+#[contracttype]
+pub struct PurchaseReceipt {
+    pub buyer: Identifier,
+    pub price: BigInt,
+    pub item_amount: BigInt,
+    pub timestamp: u64,
+}
+
 fn get_contract_id(e: &Env) -> Identifier {
     Identifier::Contract(e.get_current_contract())
 }
@@ -67,6 +112,46 @@ fn get_slope(e: &Env) -> BigInt {
     e.data().get(key).unwrap().unwrap()
 }
 
+fn put_curve_kind(e: &Env, curve_kind: CurveKind) {
+    let key = DataKey::CurveKind;
+    e.data().set(key, curve_kind);
+}
+
+fn get_curve_kind(e: &Env) -> CurveKind {
+    let key = DataKey::CurveKind;
+    e.data().get_unchecked(key).unwrap()
+}
+
+fn put_decay_numerator(e: &Env, n: BigInt) {
+    let key = DataKey::DecayNumerator;
+    e.data().set(key, n);
+}
+
+fn get_decay_numerator(e: &Env) -> BigInt {
+    let key = DataKey::DecayNumerator;
+    e.data().get(key).unwrap_or(Ok(BigInt::zero(&e))).unwrap()
+}
+
+fn put_decay_denominator(e: &Env, d: BigInt) {
+    let key = DataKey::DecayDenominator;
+    e.data().set(key, d);
+}
+
+fn get_decay_denominator(e: &Env) -> BigInt {
+    let key = DataKey::DecayDenominator;
+    e.data().get(key).unwrap_or(Ok(BigInt::zero(&e))).unwrap()
+}
+
+fn put_step(e: &Env, step: u64) {
+    let key = DataKey::Step;
+    e.data().set(key, step);
+}
+
+fn get_step(e: &Env) -> u64 {
+    let key = DataKey::Step;
+    e.data().get(key).unwrap_or(Ok(0)).unwrap()
+}
+
 fn put_starting_time(e: &Env, time: u64) {
     let key = DataKey::Timestamp;
     e.data().set(key, time);
@@ -131,6 +216,29 @@ fn write_administrator(e: &Env, id: Identifier) {
     e.data().set(key, id);
 }
 
+fn put_state(e: &Env, state: State) {
+    let key = DataKey::State;
+    e.data().set(key, state);
+}
+
+fn get_state(e: &Env) -> State {
+    let key = DataKey::State;
+    e.data().get_unchecked(key).unwrap()
+}
+
+// Checks that `admin` really is the stored admin identity *and* that `auth` is a
+// valid, unreplayed signature by that identity over `function(args)` — the same
+// signature+nonce scheme `verify_and_consume_nonce` enforces for `buy`, so that
+// knowing the admin's public `Identifier` (e.g. from the `init` event) is never
+// enough on its own to call an admin-gated entry point.
+fn check_admin(e: &Env, auth: &Auth, admin: &Identifier, function: Symbol, args: Vec<RawVal>) {
+    if admin != &read_administrator(e) {
+        panic!("not authorized by admin")
+    }
+
+    verify_and_consume_nonce(e, auth, admin, function, args);
+}
+
 fn read_nonce(e: &Env, id: &Identifier) -> BigInt {
     let key = DataKey::Nonce(id.clone());
     e.data()
@@ -139,25 +247,132 @@ fn read_nonce(e: &Env, id: &Identifier) -> BigInt {
         .unwrap()
 }
 
+fn put_nonce(e: &Env, id: &Identifier, nonce: BigInt) {
+    let key = DataKey::Nonce(id.clone());
+    e.data().set(key, nonce);
+}
+
+// Verifies that `auth` was produced by `from` for `function(args)`, checks that
+// `auth.nonce` matches the identifier's stored nonce, and consumes it by
+// writing `nonce + 1` back, so a given signature can never be replayed.
+fn verify_and_consume_nonce(
+    e: &Env,
+    auth: &Auth,
+    from: &Identifier,
+    function: Symbol,
+    args: Vec<RawVal>,
+) {
+    let auth_id = auth.sig.identifier(e);
+    if &auth_id != from {
+        panic!("signature identifier does not match from");
+    }
+
+    if auth.nonce != read_nonce(e, from) {
+        panic!("incorrect nonce");
+    }
+
+    soroban_auth::verify(e, &auth.sig, function, args);
+
+    put_nonce(e, from, auth.nonce.clone() + BigInt::from_u32(e, 1));
+}
+
+fn put_instant_price(e: &Env, price: BigInt) {
+    let key = DataKey::InstantPrice;
+    e.data().set(key, price);
+}
+
+// Zero means instant-buy is disabled for this auction.
+fn get_instant_price(e: &Env) -> BigInt {
+    let key = DataKey::InstantPrice;
+    e.data().get(key).unwrap_or(Ok(BigInt::zero(e))).unwrap()
+}
+
+fn put_last_sale(e: &Env, receipt: PurchaseReceipt) {
+    let key = DataKey::LastSale;
+    e.data().set(key, receipt);
+}
+
+fn get_last_sale(e: &Env) -> Option<PurchaseReceipt> {
+    let key = DataKey::LastSale;
+    e.data().get(key).map(|r| r.unwrap())
+}
+
+fn publish_purchase(e: &Env, buyer: Identifier, price: BigInt, item_amount: BigInt) {
+    let receipt = PurchaseReceipt {
+        buyer,
+        price,
+        item_amount,
+        timestamp: e.ledger().timestamp(),
+    };
+
+    e.events().publish((symbol!("purchase"),), receipt.clone());
+    put_last_sale(e, receipt);
+}
+
+fn publish_settled(e: &Env) {
+    e.events()
+        .publish((symbol!("settled"),), e.ledger().timestamp());
+}
+
 fn compute_price(e: &Env) -> BigInt {
     let starting_price = get_starting_price(e);
     let minimum_price = get_minimum_price(e);
     let starting_time = get_starting_time(e);
     let current_time = e.ledger().timestamp();
     let elapsed_time = current_time - starting_time;
-    let rev_slope = get_slope(e);
 
-    let computed = starting_price - BigInt::from_u64(e, elapsed_time) / rev_slope;
+    match get_curve_kind(e) {
+        CurveKind::Linear => {
+            let rev_slope = get_slope(e);
+            let computed = starting_price - BigInt::from_u64(e, elapsed_time) / rev_slope;
 
-    if computed < minimum_price {
-        minimum_price
+            if computed < minimum_price {
+                minimum_price
+            } else {
+                computed
+            }
+        }
+        CurveKind::Exponential => {
+            compute_exponential_price(e, starting_price, minimum_price, elapsed_time)
+        }
+    }
+}
+
+// price(t) = minimum_price + (starting_price - minimum_price) * (n/d)^(t / step), computed
+// with integer exponentiation by repeated multiplication so as not to require floating point.
+fn compute_exponential_price(
+    e: &Env,
+    starting_price: BigInt,
+    minimum_price: BigInt,
+    elapsed_time: u64,
+) -> BigInt {
+    let step = get_step(e);
+    let n = get_decay_numerator(e);
+    let d = get_decay_denominator(e);
+
+    let steps = if step == 0 {
+        0
     } else {
-        computed
+        (elapsed_time / step).min(MAX_DECAY_STEPS)
+    };
+
+    let mut delta = starting_price - minimum_price.clone();
+    let mut i = 0;
+    while i < steps {
+        if delta <= BigInt::zero(e) {
+            break;
+        }
+
+        delta = delta * n.clone() / d.clone();
+        i += 1;
     }
+
+    minimum_price + delta
 }
 
 pub trait AuctionContractTrait {
-    // Sets the admin, the auction's token id, the prize item id, the starting auction price, the minimum auction price, and an "inverse slope" \( \delta_time / slope \)
+    // Sets the admin, the auction's token id, the prize item id, the starting per-unit auction price, the minimum per-unit auction price, an "inverse slope" \( \delta_time / slope \) for the Linear curve, the (n, d, step) decay parameters for the Exponential curve, and an optional fixed "buy it now" instant price (zero disables it)
+    #[allow(clippy::too_many_arguments)]
     fn initialize(
         e: Env,
         admin: Identifier,
@@ -166,16 +381,36 @@ pub trait AuctionContractTrait {
         starting_price: BigInt,
         minimum_price: BigInt,
         slope: BigInt,
+        curve_kind: CurveKind,
+        decay_numerator: BigInt,
+        decay_denominator: BigInt,
+        step: u64,
+        instant_price: BigInt,
     );
 
     // Returns the nonce for the admin
     fn nonce(e: Env) -> BigInt;
 
-    // user "from" enters the auction at its current price
-    fn buy(e: Env, from: Identifier);
+    // admin starts the clock once the prize has been deposited, moving the auction from Pending to Active; `auth` must be a valid, unreplayed signature by `admin`
+    fn activate(e: Env, auth: Auth, admin: Identifier);
+
+    // admin calls off the auction, returning the prize (if any) and moving it to Cancelled; `auth` must be a valid, unreplayed signature by `admin`
+    fn cancel(e: Env, auth: Auth, admin: Identifier);
 
-    // fetch the current price of the auction
-    fn get_price(e: Env) -> BigInt;
+    // user "from" enters the auction at its current price, buying the whole remaining prize, as long as the live per-unit price is at most `max_price`; `auth` must be a valid, unreplayed signature by `from`
+    fn buy(e: Env, auth: Auth, from: Identifier, max_price: BigInt);
+
+    // user "from" buys only `item_amount` of the remaining prize at its current per-unit price, leaving the rest for later buyers; `auth` must be a valid, unreplayed signature by `from`
+    fn buy_partial(e: Env, auth: Auth, from: Identifier, item_amount: BigInt);
+
+    // user "from" immediately settles the auction at the fixed instant price, bypassing the descending curve; `auth` must be a valid, unreplayed signature by `from`
+    fn buy_now(e: Env, auth: Auth, from: Identifier);
+
+    // fetch the current descending price and the instant "buy it now" price (zero if disabled) of the auction
+    fn get_price(e: Env) -> (BigInt, BigInt);
+
+    // returns the receipt of the most recent purchase, if any sale has happened yet
+    fn last_sale(e: Env) -> Option<PurchaseReceipt>;
 }
 
 pub struct AuctionContract;
@@ -190,33 +425,186 @@ impl AuctionContractTrait for AuctionContract {
         starting_price: BigInt,
         minimum_price: BigInt,
         slope: BigInt,
+        curve_kind: CurveKind,
+        decay_numerator: BigInt,
+        decay_denominator: BigInt,
+        step: u64,
+        instant_price: BigInt,
     ) {
         if has_administrator(&e) {
             panic!("admin is already set");
         }
 
+        if curve_kind == CurveKind::Exponential {
+            if decay_denominator <= BigInt::zero(&e) {
+                panic!("decay denominator must be positive");
+            }
+
+            if decay_numerator < BigInt::zero(&e) {
+                panic!("decay numerator must not be negative");
+            }
+
+            if decay_numerator >= decay_denominator {
+                panic!("decay numerator must be smaller than the denominator");
+            }
+
+            if step == 0 {
+                panic!("step must be positive");
+            }
+        }
+
         let time = e.ledger().timestamp();
 
-        write_administrator(&e, admin);
-        put_token_id(&e, token_id);
-        put_item_id(&e, item_id);
-        put_starting_price(&e, starting_price);
+        write_administrator(&e, admin.clone());
+        put_token_id(&e, token_id.clone());
+        put_item_id(&e, item_id.clone());
+        put_starting_price(&e, starting_price.clone());
         put_starting_time(&e, time);
-        put_minimum_price(&e, minimum_price);
+        put_minimum_price(&e, minimum_price.clone());
         put_slope(&e, slope);
+        put_instant_price(&e, instant_price);
+        put_curve_kind(&e, curve_kind.clone());
+        put_decay_numerator(&e, decay_numerator);
+        put_decay_denominator(&e, decay_denominator);
+        put_step(&e, step);
+        put_state(&e, State::Pending);
+
+        e.events().publish(
+            (symbol!("init"),),
+            (admin, token_id, item_id, starting_price, minimum_price, curve_kind),
+        );
     }
 
     fn nonce(e: Env) -> BigInt {
         read_nonce(&e, &read_administrator(&e))
     }
 
-    fn buy(e: Env, from: Identifier) {
+    fn activate(e: Env, auth: Auth, admin: Identifier) {
+        let args: Vec<RawVal> = (admin.clone(), auth.nonce.clone()).into_val(&e);
+        check_admin(&e, &auth, &admin, symbol!("activate"), args);
+
+        if get_state(&e) != State::Pending {
+            panic!("auction is not pending");
+        }
+
+        let client = token::Client::new(&e, get_item_id(&e));
+        if client.balance(&get_contract_id(&e)) <= BigInt::zero(&e) {
+            panic!("prize has not been deposited yet");
+        }
+
+        put_starting_time(&e, e.ledger().timestamp());
+        put_state(&e, State::Active);
+    }
+
+    fn cancel(e: Env, auth: Auth, admin: Identifier) {
+        let args: Vec<RawVal> = (admin.clone(), auth.nonce.clone()).into_val(&e);
+        check_admin(&e, &auth, &admin, symbol!("cancel"), args);
+
+        if get_state(&e) == State::Settled || get_state(&e) == State::Cancelled {
+            panic!("auction is already finalized");
+        }
+
+        empty_contract(&e, read_administrator(&e));
+        put_state(&e, State::Cancelled);
+    }
+
+    fn buy(e: Env, auth: Auth, from: Identifier, max_price: BigInt) {
+        if get_state(&e) != State::Active {
+            panic!("auction is not active");
+        }
+
         let price = compute_price(&e);
-        transfer_to_admin(&e, &from, price);
-        empty_contract(&e, from);
+        if price > max_price {
+            panic!("price exceeds max_price");
+        }
+
+        // The signature is bound to `max_price`, a caller-chosen slippage bound, rather
+        // than the live `price` itself: a signer can't predict the exact ledger
+        // timestamp their transaction lands at, so signing over the live price would
+        // make every signature stale by the time it's submitted.
+        let args: Vec<RawVal> = (from.clone(), auth.nonce.clone(), max_price.clone()).into_val(&e);
+        verify_and_consume_nonce(&e, &auth, &from, symbol!("buy"), args);
+
+        let item_client = token::Client::new(&e, get_item_id(&e));
+        let item_amount = item_client.balance(&get_contract_id(&e));
+
+        // `compute_price` is a per-unit rate, same as `buy_partial` charges; `buy`
+        // just happens to always buy the entire remaining prize in one go.
+        transfer_to_admin(&e, &from, item_amount.clone() * price.clone());
+        empty_contract(&e, from.clone());
+        put_state(&e, State::Settled);
+
+        publish_purchase(&e, from, price, item_amount);
+        publish_settled(&e);
+    }
+
+    fn buy_partial(e: Env, auth: Auth, from: Identifier, item_amount: BigInt) {
+        if get_state(&e) != State::Active {
+            panic!("auction is not active");
+        }
+
+        if item_amount <= BigInt::zero(&e) {
+            panic!("item amount must be positive");
+        }
+
+        let item_client = token::Client::new(&e, get_item_id(&e));
+        let remaining = item_client.balance(&get_contract_id(&e));
+        if item_amount > remaining {
+            panic!("not enough supply left");
+        }
+
+        let price = compute_price(&e);
+        let args: Vec<RawVal> = (from.clone(), auth.nonce.clone(), item_amount.clone()).into_val(&e);
+        verify_and_consume_nonce(&e, &auth, &from, symbol!("buy_partial"), args);
+
+        transfer_to_admin(&e, &from, item_amount.clone() * price.clone());
+        item_client.xfer(&Signature::Invoker, &BigInt::zero(&e), &from, &item_amount);
+
+        let settled = item_amount == remaining;
+        if settled {
+            put_state(&e, State::Settled);
+        }
+
+        publish_purchase(&e, from, price, item_amount);
+        if settled {
+            publish_settled(&e);
+        }
+    }
+
+    fn buy_now(e: Env, auth: Auth, from: Identifier) {
+        if get_state(&e) != State::Active {
+            panic!("auction is not active");
+        }
+
+        let instant_price = get_instant_price(&e);
+        if instant_price <= BigInt::zero(&e) {
+            panic!("instant buy is not enabled for this auction");
+        }
+
+        let args: Vec<RawVal> =
+            (from.clone(), auth.nonce.clone(), instant_price.clone()).into_val(&e);
+        verify_and_consume_nonce(&e, &auth, &from, symbol!("buy_now"), args);
+
+        let item_client = token::Client::new(&e, get_item_id(&e));
+        let item_amount = item_client.balance(&get_contract_id(&e));
+
+        transfer_to_admin(&e, &from, instant_price.clone());
+        empty_contract(&e, from.clone());
+        put_state(&e, State::Settled);
+
+        publish_purchase(&e, from, instant_price, item_amount);
+        publish_settled(&e);
+    }
+
+    fn get_price(e: Env) -> (BigInt, BigInt) {
+        if get_state(&e) != State::Active {
+            panic!("auction is not active");
+        }
+
+        (compute_price(&e), get_instant_price(&e))
     }
 
-    fn get_price(e: Env) -> BigInt {
-        compute_price(&e)
+    fn last_sale(e: Env) -> Option<PurchaseReceipt> {
+        get_last_sale(&e)
     }
 }
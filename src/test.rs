@@ -2,6 +2,7 @@
 
 use crate::testutils::{register_test_contract as register_auction, AuctionContract};
 use crate::token::{self, TokenMetadata};
+use crate::{Auth, CurveKind};
 use rand::{thread_rng, RngCore};
 use soroban_auth::{Identifier, Signature};
 use soroban_sdk::bigint;
@@ -44,6 +45,7 @@ fn create_usdc_contract(e: &Env, admin: &AccountId) -> ([u8; 32], token::Client)
     (id.into(), token)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_auction_contract(
     e: &Env,
     admin: &AccountId,
@@ -63,10 +65,420 @@ fn create_auction_contract(
         starting_price,
         minimum_price,
         slope,
+        CurveKind::Linear,
+        BigInt::zero(e),
+        BigInt::zero(e),
+        0,
+        BigInt::zero(e),
     );
     (id, auction)
 }
 
+// Shared fixture for the feature-specific tests below: a freshly initialized
+// auction (starting price 5, minimum price 1, slope 900) with its 10-unit
+// prize already deposited but not yet `activate`d, and a buyer pre-funded
+// with 1000 usdc.
+struct AuctionSetup {
+    e: Env,
+    usdc_admin: AccountId,
+    admin: AccountId,
+    admin_id: Identifier,
+    buyer: AccountId,
+    buyer_id: Identifier,
+    usdc_token: token::Client,
+    item_token: token::Client,
+    auction: AuctionContract,
+    auction_id: Identifier,
+}
+
+impl AuctionSetup {
+    // admin-gated calls need a fresh signature over the admin's current nonce each time
+    fn admin_auth(&self) -> Auth {
+        Auth {
+            sig: Signature::Invoker,
+            nonce: self.auction.nonce(),
+        }
+    }
+
+    fn activate(&self) {
+        let auth = self.admin_auth();
+        self.auction.activate(&self.admin, auth, self.admin_id.clone());
+    }
+
+    fn cancel(&self) {
+        let auth = self.admin_auth();
+        self.auction.cancel(&self.admin, auth, self.admin_id.clone());
+    }
+}
+
+fn setup_auction(
+    curve_kind: CurveKind,
+    decay_numerator: u32,
+    decay_denominator: u32,
+    step: u64,
+    instant_price: u32,
+) -> AuctionSetup {
+    let e: Env = Default::default();
+    let usdc_admin = e.accounts().generate();
+    let item_admin = e.accounts().generate();
+    let admin = e.accounts().generate();
+    let admin_id = Identifier::Account(admin.clone());
+    let buyer = e.accounts().generate();
+    let buyer_id = Identifier::Account(buyer.clone());
+
+    e.ledger().set(LedgerInfo {
+        timestamp: 1666359075,
+        protocol_version: 1,
+        sequence_number: 10,
+        network_passphrase: Default::default(),
+        base_reserve: 10,
+    });
+
+    let (usdc_id, usdc_token) = create_usdc_contract(&e, &usdc_admin);
+    let (item_id, item_token) = create_test_token_contract(&e, &item_admin);
+
+    let contract_id = generate_contract_id();
+    register_auction(&e, &contract_id);
+    let auction = AuctionContract::new(&e, &contract_id);
+    auction.initialize(
+        &admin_id,
+        &usdc_id,
+        &item_id,
+        BigInt::from_u32(&e, 5),
+        BigInt::from_u32(&e, 1),
+        bigint!(&e, 900),
+        curve_kind,
+        BigInt::from_u32(&e, decay_numerator),
+        BigInt::from_u32(&e, decay_denominator),
+        step,
+        BigInt::from_u32(&e, instant_price),
+    );
+    let auction_id = Identifier::Contract(BytesN::from_array(&e, &contract_id));
+
+    // fund and deposit the prize
+    item_token.with_source_account(&item_admin).mint(
+        &Signature::Invoker,
+        &BigInt::zero(&e),
+        &admin_id,
+        &BigInt::from_u32(&e, 10),
+    );
+    item_token.with_source_account(&admin).xfer(
+        &Signature::Invoker,
+        &BigInt::zero(&e),
+        &auction_id,
+        &BigInt::from_u32(&e, 10),
+    );
+
+    // fund the buyer
+    usdc_token.with_source_account(&usdc_admin).mint(
+        &Signature::Invoker,
+        &BigInt::zero(&e),
+        &buyer_id,
+        &BigInt::from_u32(&e, 1000),
+    );
+
+    AuctionSetup {
+        e,
+        usdc_admin,
+        admin,
+        admin_id,
+        buyer,
+        buyer_id,
+        usdc_token,
+        item_token,
+        auction,
+        auction_id,
+    }
+}
+
+#[test]
+#[should_panic(expected = "auction is not active")]
+fn buy_before_activate_panics() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy(
+        &setup.buyer,
+        auth,
+        setup.buyer_id.clone(),
+        BigInt::from_u32(&setup.e, 1000),
+    );
+}
+
+#[test]
+fn cancel_returns_the_prize_to_the_admin() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+    setup.activate();
+
+    // the prize left the admin's balance once it was deposited...
+    assert_eq!(setup.item_token.balance(&setup.admin_id), 0);
+
+    setup.cancel();
+
+    // ...and cancel hands it back
+    assert_eq!(setup.item_token.balance(&setup.admin_id), 10);
+}
+
+#[test]
+#[should_panic(expected = "auction is not active")]
+fn buy_after_settlement_panics() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+    setup.activate();
+
+    let (price, _) = setup.auction.get_price();
+    setup.usdc_token.with_source_account(&setup.buyer).approve(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &setup.auction_id,
+        &(BigInt::from_u32(&setup.e, 10) * price.clone()),
+    );
+
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup
+        .auction
+        .buy(&setup.buyer, auth, setup.buyer_id.clone(), price);
+
+    // the auction just settled; a second buy must panic rather than charge again
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::from_u32(&setup.e, 1),
+    };
+    setup.auction.buy(
+        &setup.buyer,
+        auth,
+        setup.buyer_id.clone(),
+        BigInt::from_u32(&setup.e, 1000),
+    );
+}
+
+#[test]
+fn buy_partial_across_multiple_buyers_exhausts_into_settled() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+    setup.activate();
+
+    let buyer2 = setup.e.accounts().generate();
+    let buyer2_id = Identifier::Account(buyer2.clone());
+    setup.usdc_token.with_source_account(&setup.usdc_admin).mint(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &buyer2_id,
+        &BigInt::from_u32(&setup.e, 1000),
+    );
+
+    // advance the ledger so the per-unit price has decayed to 3 (5 - 1800/900)
+    setup.e.ledger().set(LedgerInfo {
+        timestamp: 1666360875,
+        protocol_version: 1,
+        sequence_number: 10,
+        network_passphrase: Default::default(),
+        base_reserve: 10,
+    });
+    let (price, _) = setup.auction.get_price();
+    assert_eq!(price, BigInt::from_u32(&setup.e, 3));
+
+    // buyer1 takes 4 of the 10 units at 3/unit
+    setup.usdc_token.with_source_account(&setup.buyer).approve(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &setup.auction_id,
+        &(BigInt::from_u32(&setup.e, 4) * price.clone()),
+    );
+    let buyer1_auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_partial(
+        &setup.buyer,
+        buyer1_auth,
+        setup.buyer_id.clone(),
+        BigInt::from_u32(&setup.e, 4),
+    );
+
+    assert_eq!(setup.item_token.balance(&setup.buyer_id), 4);
+    assert_eq!(setup.usdc_token.balance(&setup.admin_id), 12);
+
+    // buyer2 takes the remaining 6 units, which exhausts the prize and settles the auction
+    setup.usdc_token.with_source_account(&buyer2).approve(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &setup.auction_id,
+        &(BigInt::from_u32(&setup.e, 6) * price.clone()),
+    );
+    let buyer2_auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_partial(
+        &buyer2,
+        buyer2_auth,
+        buyer2_id.clone(),
+        BigInt::from_u32(&setup.e, 6),
+    );
+
+    assert_eq!(setup.item_token.balance(&buyer2_id), 6);
+    assert_eq!(setup.usdc_token.balance(&setup.admin_id), 30);
+    assert_eq!(setup.item_token.balance(&setup.auction_id), 0);
+}
+
+#[test]
+#[should_panic(expected = "incorrect nonce")]
+fn replaying_a_consumed_nonce_is_rejected() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+    setup.activate();
+
+    let (price, _) = setup.auction.get_price();
+    setup.usdc_token.with_source_account(&setup.buyer).approve(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &setup.auction_id,
+        &(BigInt::from_u32(&setup.e, 2) * price.clone()),
+    );
+
+    // the first buy_partial consumes nonce 0...
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_partial(
+        &setup.buyer,
+        auth,
+        setup.buyer_id.clone(),
+        BigInt::from_u32(&setup.e, 1),
+    );
+
+    // ...so replaying the same nonce 0 on a second call must be rejected
+    let replayed_auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_partial(
+        &setup.buyer,
+        replayed_auth,
+        setup.buyer_id.clone(),
+        BigInt::from_u32(&setup.e, 1),
+    );
+}
+
+#[test]
+fn exponential_curve_decays_geometrically_towards_the_minimum() {
+    // starting price 5, minimum price 1, n/d = 3/4 decay per 600s step
+    let setup = setup_auction(CurveKind::Exponential, 3, 4, 600, 0);
+    setup.activate();
+
+    // 1800s elapsed is 3 steps: delta 4 -> 3 -> 2 -> 1, so price = 1 + 1 = 2
+    setup.e.ledger().set(LedgerInfo {
+        timestamp: 1666360875,
+        protocol_version: 1,
+        sequence_number: 10,
+        network_passphrase: Default::default(),
+        base_reserve: 10,
+    });
+    let (price, _) = setup.auction.get_price();
+    assert_eq!(price, BigInt::from_u32(&setup.e, 2));
+
+    // past MAX_DECAY_STEPS worth of steps the price has fully decayed to the minimum
+    setup.e.ledger().set(LedgerInfo {
+        timestamp: 1666359075 + 600 * 200,
+        protocol_version: 1,
+        sequence_number: 10,
+        network_passphrase: Default::default(),
+        base_reserve: 10,
+    });
+    let (price, _) = setup.auction.get_price();
+    assert_eq!(price, BigInt::from_u32(&setup.e, 1));
+}
+
+#[test]
+fn last_sale_reports_the_most_recent_purchase_receipt() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+    setup.activate();
+
+    // no purchase has happened yet
+    assert!(setup.auction.last_sale().is_none());
+
+    setup.e.ledger().set(LedgerInfo {
+        timestamp: 1666360875,
+        protocol_version: 1,
+        sequence_number: 10,
+        network_passphrase: Default::default(),
+        base_reserve: 10,
+    });
+    let (price, _) = setup.auction.get_price();
+    setup.usdc_token.with_source_account(&setup.buyer).approve(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &setup.auction_id,
+        &(BigInt::from_u32(&setup.e, 4) * price.clone()),
+    );
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_partial(
+        &setup.buyer,
+        auth,
+        setup.buyer_id.clone(),
+        BigInt::from_u32(&setup.e, 4),
+    );
+
+    let receipt = setup.auction.last_sale().expect("a sale just happened");
+    assert_eq!(receipt.buyer, setup.buyer_id);
+    assert_eq!(receipt.price, price);
+    assert_eq!(receipt.item_amount, BigInt::from_u32(&setup.e, 4));
+    assert_eq!(receipt.timestamp, 1666360875);
+}
+
+#[test]
+fn buy_now_charges_the_flat_instant_price_for_the_whole_prize() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 50);
+    setup.activate();
+
+    // advance time so the descending price (would be 3/unit here) is clearly not
+    // what gets charged; buy_now must charge the flat instant_price regardless
+    setup.e.ledger().set(LedgerInfo {
+        timestamp: 1666360875,
+        protocol_version: 1,
+        sequence_number: 10,
+        network_passphrase: Default::default(),
+        base_reserve: 10,
+    });
+
+    setup.usdc_token.with_source_account(&setup.buyer).approve(
+        &Signature::Invoker,
+        &BigInt::zero(&setup.e),
+        &setup.auction_id,
+        &BigInt::from_u32(&setup.e, 50),
+    );
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_now(&setup.buyer, auth, setup.buyer_id.clone());
+
+    assert_eq!(setup.usdc_token.balance(&setup.buyer_id), 1000 - 50);
+    assert_eq!(setup.usdc_token.balance(&setup.admin_id), 50);
+    assert_eq!(setup.item_token.balance(&setup.buyer_id), 10);
+}
+
+#[test]
+#[should_panic(expected = "instant buy is not enabled for this auction")]
+fn buy_now_panics_when_no_instant_price_was_configured() {
+    let setup = setup_auction(CurveKind::Linear, 0, 0, 0, 0);
+    setup.activate();
+
+    let auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&setup.e),
+    };
+    setup.auction.buy_now(&setup.buyer, auth, setup.buyer_id.clone());
+}
+
 #[test]
 fn test() {
     let e: Env = Default::default();
@@ -120,6 +532,14 @@ fn test() {
         &BigInt::from_u32(&e, 10),
     );
 
+    // user1 activates the auction now that the prize has been deposited, authorizing
+    // itself as its own invoker with its current (first) nonce
+    let user1_auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&e),
+    };
+    auction.activate(&user1, user1_auth, user1_id.clone());
+
     // minting 1000 usdc to user2
     usdc_token.with_source_account(&admin1).mint(
         &Signature::Invoker,
@@ -137,22 +557,29 @@ fn test() {
         base_reserve: 10,
     });
 
-    // user2 deposits \(starting_price - (\delta_time / slope) \) usdc into auction, so 3 usdc \((5 - (1800 / 900)) = 3\)
+    // the per-unit price is \(starting_price - (\delta_time / slope) \), so 3 usdc \((5 - (1800 / 900)) = 3\); `buy` takes
+    // the whole 10-unit prize, so user2 must approve \(10 \times 3 = 30\) usdc
+    let (per_unit_price, _) = auction.get_price();
     usdc_token.with_source_account(&user2).approve(
         &Signature::Invoker,
         &BigInt::zero(&e),
         &auction_id,
-        &auction.get_price(),
+        &(BigInt::from_u32(&e, 10) * per_unit_price.clone()),
     );
 
-    // user2 enters the auction
-    auction.buy(user2_id.clone());
+    // user2 enters the auction, authorizing the purchase as its own invoker with its current (first) nonce
+    // and a max acceptable per-unit price equal to what it just observed
+    let user2_auth = Auth {
+        sig: Signature::Invoker,
+        nonce: BigInt::zero(&e),
+    };
+    auction.buy(&user2, user2_auth, user2_id.clone(), per_unit_price.clone());
 
-    // the buyer (user2) should have \(1000 - 3\) as usdc balance
-    assert_eq!(usdc_token.balance(&user2_id), 997);
+    // the buyer (user2) should have \(1000 - 30\) as usdc balance
+    assert_eq!(usdc_token.balance(&user2_id), 970);
 
-    // the auction admin (user1) should have \( 3\) as usdc token balance (since user one bought in the auction at a price of 3)
-    assert_eq!(usdc_token.balance(&user1_id), 3);
+    // the auction admin (user1) should have \( 30\) as usdc token balance (10 units at a per-unit price of 3)
+    assert_eq!(usdc_token.balance(&user1_id), 30);
 
     // the buyer (user2) should have \( 10 \) as TEST token balance (bought at the auction)
     assert_eq!(test_token.balance(&user2_id), 10);
@@ -1,9 +1,9 @@
 #![cfg(any(test, feature = "testutils"))]
 
-use crate::AuctionContractClient;
+use crate::{Auth, AuctionContractClient, CurveKind, PurchaseReceipt};
 use soroban_auth::Identifier;
 
-use soroban_sdk::{BigInt, BytesN, Env};
+use soroban_sdk::{AccountId, BigInt, BytesN, Env};
 
 pub fn register_test_contract(e: &Env, contract_id: &[u8; 32]) {
     let contract_id = BytesN::from_array(e, contract_id);
@@ -27,22 +27,33 @@ impl AuctionContract {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &self,
         admin: &Identifier,
         token_id: &[u8; 32],
-        //        item_id: &[u8; 32],
+        item_id: &[u8; 32],
         starting_price: BigInt,
         minimum_price: BigInt,
         slope: BigInt,
+        curve_kind: CurveKind,
+        decay_numerator: BigInt,
+        decay_denominator: BigInt,
+        step: u64,
+        instant_price: BigInt,
     ) {
         self.client().initialize(
             admin,
             &BytesN::from_array(&self.env, token_id),
-            //            &BytesN::from_array(&self.env, item_id),
+            &BytesN::from_array(&self.env, item_id),
             &starting_price,
             &minimum_price,
             &slope,
+            &curve_kind,
+            &decay_numerator,
+            &decay_denominator,
+            &step,
+            &instant_price,
         );
     }
 
@@ -50,11 +61,47 @@ impl AuctionContract {
         self.client().nonce()
     }
 
-    pub fn buy(&self, from: Identifier) -> bool {
-        self.client().buy(&from)
+    pub fn activate(&self, source: &AccountId, auth: Auth, admin: Identifier) {
+        self.client()
+            .with_source_account(source)
+            .activate(&auth, &admin);
     }
 
-    pub fn get_price(&self) -> BigInt {
+    pub fn cancel(&self, source: &AccountId, auth: Auth, admin: Identifier) {
+        self.client()
+            .with_source_account(source)
+            .cancel(&auth, &admin);
+    }
+
+    pub fn buy(&self, source: &AccountId, auth: Auth, from: Identifier, max_price: BigInt) -> bool {
+        self.client()
+            .with_source_account(source)
+            .buy(&auth, &from, &max_price)
+    }
+
+    pub fn buy_partial(
+        &self,
+        source: &AccountId,
+        auth: Auth,
+        from: Identifier,
+        item_amount: BigInt,
+    ) -> bool {
+        self.client()
+            .with_source_account(source)
+            .buy_partial(&auth, &from, &item_amount)
+    }
+
+    pub fn buy_now(&self, source: &AccountId, auth: Auth, from: Identifier) -> bool {
+        self.client()
+            .with_source_account(source)
+            .buy_now(&auth, &from)
+    }
+
+    pub fn get_price(&self) -> (BigInt, BigInt) {
         self.client().get_price()
     }
+
+    pub fn last_sale(&self) -> Option<PurchaseReceipt> {
+        self.client().last_sale()
+    }
 }